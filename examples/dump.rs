@@ -0,0 +1,35 @@
+use std::env;
+
+use victron_gx::GxDevice;
+
+/// Dumps the raw value of every readable register in a range, to discover what
+/// a GX exposes.
+///
+/// Usage: dump [unit id] [first register] [last register]
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().collect();
+    let device_id: u8 = args.get(1).map_or(Ok(100), |a| a.parse())?;
+    let first: u16 = args.get(2).map_or(Ok(800), |a| a.parse())?;
+    let last: u16 = args.get(3).map_or(Ok(900), |a| a.parse())?;
+
+    let gx = GxDevice::new("192.168.11.1:502".parse().unwrap());
+
+    for address in first..=last {
+        if let Ok(val) = gx.get_modbus_raw_u16(device_id, address).await {
+            println!(
+                "{}/{}: {} (signed: {})",
+                device_id, address, val, val as i16
+            );
+        }
+    }
+
+    let stats = gx.stats();
+    println!(
+        "{} registers read, {} unavailable",
+        stats.requests - stats.errors,
+        stats.errors
+    );
+
+    Ok(())
+}
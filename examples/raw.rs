@@ -0,0 +1,17 @@
+use tokio_modbus::prelude::*;
+use victron_gx::GxDevice;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let gx = GxDevice::new("192.168.11.1:502".parse().unwrap());
+
+    // The battery's time-to-go isn't modelled by the crate: read it by hand.
+    // The register holds the time in units of 100 s.
+    let mut ctx = gx.connect(225).await?;
+    let regs = ctx.read_holding_registers(303, 1).await??;
+    ctx.disconnect().await?;
+
+    println!("Time to go: {:.1} h", regs[0] as f32 * 100. / 3600.);
+
+    Ok(())
+}
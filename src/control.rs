@@ -0,0 +1,192 @@
+//! Ready-made control loops driving the ESS grid setpoint, and small
+//! building blocks for writing your own.
+
+use std::time::Duration;
+
+use crate::GxDevice;
+
+/// Parameters of [`GxDevice::follow_solar_surplus`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurplusParams {
+    /// Time between two control steps.
+    pub interval: Duration,
+    /// At or below this SOC (%), the battery isn't discharged to cover loads.
+    pub min_soc: f32,
+    /// Maximum power (W) put into the battery; surplus above it is exported.
+    pub max_charge_power: f32,
+}
+
+/// Grid setpoint (W, positive when importing) for one control step.
+///
+/// The PV surplus (`pv_power - load_power`) charges the battery up to
+/// `max_charge_power` and the rest is exported. A deficit is covered by the
+/// battery, unless the SOC is at or below `min_soc`, in which case it is
+/// imported from the grid.
+pub fn surplus_setpoint(params: &SurplusParams, pv_power: f32, load_power: f32, soc: f32) -> f32 {
+    let surplus = pv_power - load_power;
+
+    if surplus > params.max_charge_power {
+        -(surplus - params.max_charge_power)
+    } else if surplus < 0. && soc <= params.min_soc {
+        -surplus
+    } else {
+        0.
+    }
+}
+
+/// Decision taken by [`SocHysteresis::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    On,
+    Off,
+}
+
+/// On/off switch driven by the battery SOC with hysteresis, e.g. for a dump
+/// load: it turns on once the SOC rises above `high` and back off once it
+/// falls below `low`. Between the two thresholds it keeps its state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SocHysteresis {
+    low: f32,
+    high: f32,
+    on: bool,
+}
+
+impl SocHysteresis {
+    /// Creates a switch in the off state, with thresholds in percent.
+    pub fn new(low: f32, high: f32) -> Result<Self, anyhow::Error> {
+        if !low.is_finite() || !high.is_finite() {
+            return Err(anyhow::Error::msg("SOC thresholds must be finite"));
+        }
+        if low >= high {
+            return Err(anyhow::Error::msg(
+                "Low SOC threshold must be below the high one",
+            ));
+        }
+        Ok(SocHysteresis {
+            low,
+            high,
+            on: false,
+        })
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    /// Feeds a new SOC reading. Returns the action to apply when the state
+    /// changes, `None` otherwise.
+    pub fn step(&mut self, soc: f32) -> Option<Action> {
+        if !self.on && soc > self.high {
+            self.on = true;
+            Some(Action::On)
+        } else if self.on && soc < self.low {
+            self.on = false;
+            Some(Action::Off)
+        } else {
+            None
+        }
+    }
+}
+
+/// Setpoint written by one step of [`GxDevice::follow_solar_surplus`]:
+/// [`surplus_setpoint`] for the `(pv_power, load_power, soc)` readings, or a
+/// neutral 0 W when they couldn't be read, so a stale export setpoint isn't
+/// left in force.
+pub fn surplus_step_setpoint(params: &SurplusParams, readings: Option<(f32, f32, f32)>) -> f32 {
+    match readings {
+        Some((pv_power, load_power, soc)) => surplus_setpoint(params, pv_power, load_power, soc),
+        None => 0.,
+    }
+}
+
+impl GxDevice {
+    /// Periodically steers the ESS grid setpoint so the battery charges from
+    /// the PV surplus, see [`surplus_setpoint`].
+    ///
+    /// Never returns on its own. A step whose readings fail writes a 0 W
+    /// setpoint, see [`surplus_step_setpoint`]. When writing the setpoint
+    /// fails, a 0 W setpoint is attempted so no export setpoint stays in
+    /// force, and the error is returned. The loop also stops
+    /// when the future is dropped (e.g. in a `tokio::select!`); call
+    /// [`GxDevice::ess_release`] afterwards in that case.
+    pub async fn follow_solar_surplus(&self, params: SurplusParams) -> Result<(), anyhow::Error> {
+        loop {
+            let readings = self.surplus_readings().await.ok();
+            let setpoint = surplus_step_setpoint(&params, readings);
+            if let Err(e) = self.set_setpoint(setpoint).await {
+                let _ = self.set_setpoint(0.).await;
+                return Err(e);
+            }
+
+            tokio::time::sleep(params.interval).await;
+        }
+    }
+
+    /// PV power, load power and SOC used by one [`surplus_setpoint`] step.
+    async fn surplus_readings(&self) -> Result<(f32, f32, f32), anyhow::Error> {
+        let pv_power = self.get_total_pv_power().await?;
+        let load_power = self.get_consumption_power().await?;
+        let soc = self.get_battery_soc().await?;
+        Ok((pv_power, load_power, soc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: SurplusParams = SurplusParams {
+        interval: Duration::from_secs(5),
+        min_soc: 20.,
+        max_charge_power: 3000.,
+    };
+
+    #[test]
+    fn hysteresis_switches_on_above_high_and_off_below_low() {
+        let mut switch = SocHysteresis::new(40., 90.).unwrap();
+
+        assert_eq!(switch.step(60.), None);
+        assert_eq!(switch.step(90.), None);
+        assert_eq!(switch.step(91.), Some(Action::On));
+        assert_eq!(switch.step(95.), None);
+        assert_eq!(switch.step(60.), None);
+        assert!(switch.is_on());
+        assert_eq!(switch.step(40.), None);
+        assert_eq!(switch.step(39.), Some(Action::Off));
+        assert_eq!(switch.step(60.), None);
+        assert!(!switch.is_on());
+    }
+
+    #[test]
+    fn hysteresis_rejects_invalid_thresholds() {
+        assert!(SocHysteresis::new(90., 40.).is_err());
+        assert!(SocHysteresis::new(50., 50.).is_err());
+        assert!(SocHysteresis::new(f32::NAN, 90.).is_err());
+        assert!(SocHysteresis::new(40., f32::INFINITY).is_err());
+    }
+
+    #[test]
+    fn surplus_above_max_charge_power_is_exported() {
+        assert_eq!(surplus_setpoint(&PARAMS, 5000., 500., 50.), -1500.);
+    }
+
+    #[test]
+    fn deficit_at_min_soc_is_imported() {
+        assert_eq!(surplus_setpoint(&PARAMS, 200., 1000., 20.), 800.);
+    }
+
+    #[test]
+    fn battery_absorbs_surplus_and_deficit_otherwise() {
+        assert_eq!(surplus_setpoint(&PARAMS, 2000., 500., 50.), 0.);
+        assert_eq!(surplus_setpoint(&PARAMS, 200., 1000., 50.), 0.);
+    }
+
+    #[test]
+    fn failed_readings_fall_back_to_a_neutral_setpoint() {
+        assert_eq!(surplus_step_setpoint(&PARAMS, None), 0.);
+        assert_eq!(
+            surplus_step_setpoint(&PARAMS, Some((5000., 500., 50.))),
+            -1500.
+        );
+    }
+}
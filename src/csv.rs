@@ -0,0 +1,135 @@
+//! Minimal CSV logging of the core readings.
+
+use std::{
+    io::{self, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{format_value, GxDevice};
+
+const COLUMNS: [&str; 6] = [
+    "input_power",
+    "output_power",
+    "battery_power",
+    "battery_soc",
+    "solar_power",
+    "setpoint",
+];
+
+/// Appends the core readings to a CSV sink.
+///
+/// The header row is written on the first call to [`CsvLogger::log`]. A row
+/// is only appended when at least one value differs from the previous row;
+/// failed readings are left as empty cells.
+pub struct CsvLogger<W: Write> {
+    sink: W,
+    header_written: bool,
+    last_row: Option<Vec<Option<f32>>>,
+}
+
+impl<W: Write> CsvLogger<W> {
+    pub fn new(sink: W) -> Self {
+        CsvLogger {
+            sink,
+            header_written: false,
+            last_row: None,
+        }
+    }
+
+    /// Reads the core values from `gx` and appends a row timestamped with the
+    /// current Unix time in seconds, if anything changed.
+    pub async fn log(&mut self, gx: &GxDevice) -> io::Result<()> {
+        let row = vec![
+            gx.get_input_power().await.ok(),
+            gx.get_output_power().await.ok(),
+            gx.get_battery_power().await.ok(),
+            gx.get_battery_soc().await.ok(),
+            gx.get_solar_power().await.ok(),
+            gx.get_setpoint().await.ok(),
+        ];
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.write_row(timestamp, row, gx.export_precision)
+    }
+
+    /// Writes the header if needed, then `row` unless it equals the last one.
+    fn write_row(
+        &mut self,
+        timestamp: u64,
+        row: Vec<Option<f32>>,
+        precision: Option<usize>,
+    ) -> io::Result<()> {
+        if !self.header_written {
+            writeln!(self.sink, "timestamp,{}", COLUMNS.join(","))?;
+            self.header_written = true;
+        }
+
+        if self.last_row.as_ref() == Some(&row) {
+            return Ok(());
+        }
+
+        let cells: Vec<String> = row
+            .iter()
+            .map(|value| {
+                value
+                    .map(|v| format_value(v, precision))
+                    .unwrap_or_default()
+            })
+            .collect();
+        writeln!(self.sink, "{},{}", timestamp, cells.join(","))?;
+        self.sink.flush()?;
+
+        self.last_row = Some(row);
+        Ok(())
+    }
+
+    /// Returns the underlying sink.
+    pub fn into_inner(self) -> W {
+        self.sink
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_header_once_and_skips_unchanged_rows() {
+        let mut logger = CsvLogger::new(Vec::new());
+        let row = vec![
+            Some(100.),
+            Some(200.),
+            Some(-50.),
+            Some(80.),
+            Some(0.),
+            Some(0.),
+        ];
+
+        logger.write_row(1, row.clone(), None).unwrap();
+        logger.write_row(2, row.clone(), None).unwrap();
+        let mut changed = row;
+        changed[3] = Some(81.);
+        logger.write_row(3, changed, None).unwrap();
+
+        assert_eq!(
+            String::from_utf8(logger.into_inner()).unwrap(),
+            "timestamp,input_power,output_power,battery_power,battery_soc,solar_power,setpoint\n\
+             1,100,200,-50,80,0,0\n\
+             3,100,200,-50,81,0,0\n"
+        );
+    }
+
+    #[test]
+    fn leaves_failed_readings_empty() {
+        let mut logger = CsvLogger::new(Vec::new());
+        let row = vec![Some(1.26), None, None, Some(80.), None, None];
+
+        logger.write_row(1, row, Some(1)).unwrap();
+
+        let out = String::from_utf8(logger.into_inner()).unwrap();
+        assert_eq!(out.lines().nth(1), Some("1,1.3,,,80.0,,"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Minimal HTTP status endpoint.
+
+use std::{net::SocketAddr, time::Duration};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::{format_value, GxDevice};
+
+/// Time a client gets to send its request and to receive the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl GxDevice {
+    /// Serves the core readings over HTTP: a JSON object at `/` and the
+    /// Prometheus metrics at `/metrics`.
+    ///
+    /// Connections are handled one at a time and every request reads fresh
+    /// values from the GX. A client that doesn't send its request or read the
+    /// response within 5 s is dropped. Only returns if binding fails.
+    pub async fn serve_status(&self, addr: SocketAddr) -> Result<(), anyhow::Error> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve(listener).await
+    }
+
+    async fn serve(&self, listener: TcpListener) -> Result<(), anyhow::Error> {
+        loop {
+            let mut stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                // Typically out of file descriptors: back off and retry.
+                Err(_) => {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+
+            let mut buf = [0; 1024];
+            let n = match tokio::time::timeout(CLIENT_TIMEOUT, stream.read(&mut buf)).await {
+                Ok(Ok(n)) => n,
+                _ => continue,
+            };
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+            let (status, content_type, body) = match path {
+                "/" => ("200 OK", "application/json", self.render_json().await),
+                "/metrics" => (
+                    "200 OK",
+                    "text/plain; version=0.0.4",
+                    self.render_prometheus().await,
+                ),
+                _ => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+            };
+
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                content_type,
+                body.len(),
+                body
+            );
+            let _ =
+                tokio::time::timeout(CLIENT_TIMEOUT, stream.write_all(response.as_bytes())).await;
+        }
+    }
+
+    /// Core readings as a flat JSON object; failed readings are `null`.
+    async fn render_json(&self) -> String {
+        let readings = [
+            ("input_power", self.get_input_power().await),
+            ("output_power", self.get_output_power().await),
+            ("battery_power", self.get_battery_power().await),
+            ("battery_soc", self.get_battery_soc().await),
+            ("solar_power", self.get_solar_power().await),
+            ("setpoint", self.get_setpoint().await),
+        ];
+
+        let fields: Vec<String> = readings
+            .iter()
+            .map(|(name, value)| match value {
+                Ok(value) => format!(
+                    "\"{}\":{}",
+                    name,
+                    format_value(*value, self.export_precision)
+                ),
+                Err(_) => format!("\"{}\":null", name),
+            })
+            .collect();
+        format!("{{{}}}\n", fields.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpStream;
+
+    #[tokio::test]
+    async fn answers_unknown_paths_with_404() {
+        let gx = GxDevice::new("127.0.0.1:1".parse().unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = async {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream
+                .write_all(b"GET /nope HTTP/1.1\r\n\r\n")
+                .await
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        let response = tokio::select! {
+            _ = gx.serve(listener) => unreachable!(),
+            response = client => response,
+        };
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(response.ends_with("\r\n\r\nNot found\n"));
+    }
+}
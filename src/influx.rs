@@ -0,0 +1,125 @@
+//! InfluxDB line-protocol output of the core readings.
+
+use crate::{format_value, GxDevice, GX_CAN_BUS_BMS, GX_VE_DEVICE_0};
+
+/// Subsystem name, Modbus unit id and named readings of one line.
+type Subsystem = (
+    &'static str,
+    u8,
+    Vec<(&'static str, Result<f32, anyhow::Error>)>,
+);
+
+/// Escapes a measurement name: commas and spaces must be backslash-escaped.
+fn escape_measurement(measurement: &str) -> String {
+    measurement.replace(',', "\\,").replace(' ', "\\ ")
+}
+
+/// Renders one line per subsystem with at least one successful reading.
+fn render_lines(
+    measurement: &str,
+    subsystems: Vec<Subsystem>,
+    timestamp: Option<i64>,
+    precision: Option<usize>,
+) -> String {
+    let measurement = escape_measurement(measurement);
+    let mut out = String::new();
+    for (subsystem, unit_id, fields) in subsystems {
+        let fields: Vec<String> = fields
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value
+                    .ok()
+                    .map(|v| format!("{}={}", name, format_value(v, precision)))
+            })
+            .collect();
+        if fields.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!(
+            "{},subsystem={},unit_id={} {}",
+            measurement,
+            subsystem,
+            unit_id,
+            fields.join(",")
+        ));
+        if let Some(timestamp) = timestamp {
+            out.push_str(&format!(" {}", timestamp));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+impl GxDevice {
+    /// Renders the core readings as InfluxDB line protocol, one line per
+    /// subsystem, tagged with the subsystem name and Modbus unit id.
+    ///
+    /// Readings that fail are omitted, and a subsystem without any successful
+    /// reading produces no line. `timestamp` is appended as-is, so it must be
+    /// in the precision the receiving end expects.
+    pub async fn to_line_protocol(&self, measurement: &str, timestamp: Option<i64>) -> String {
+        let subsystems = vec![
+            (
+                "vebus",
+                self.vebus_device,
+                vec![
+                    ("input_power", self.get_input_power().await),
+                    ("output_power", self.get_output_power().await),
+                ],
+            ),
+            (
+                "battery",
+                GX_CAN_BUS_BMS,
+                vec![
+                    ("power", self.get_battery_power().await),
+                    ("soc", self.get_battery_soc().await),
+                ],
+            ),
+            (
+                "system",
+                GX_VE_DEVICE_0,
+                vec![
+                    ("pv_power", self.get_total_pv_power().await),
+                    ("setpoint", self.get_setpoint().await),
+                ],
+            ),
+        ];
+
+        render_lines(measurement, subsystems, timestamp, self.export_precision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_commas_and_spaces() {
+        assert_eq!(escape_measurement("victron gx,home"), "victron\\ gx\\,home");
+    }
+
+    #[test]
+    fn renders_lines_and_skips_empty_subsystems() {
+        let subsystems = vec![
+            (
+                "battery",
+                225,
+                vec![
+                    ("power", Ok(-250.)),
+                    ("soc", Err(anyhow::Error::msg("timeout"))),
+                ],
+            ),
+            (
+                "system",
+                100,
+                vec![("setpoint", Err(anyhow::Error::msg("timeout")))],
+            ),
+        ];
+
+        assert_eq!(
+            render_lines("my gx", subsystems, Some(1700000000), None),
+            "my\\ gx,subsystem=battery,unit_id=225 power=-250 1700000000\n"
+        );
+    }
+}
@@ -1,14 +1,268 @@
-use std::{fmt::Error, net::SocketAddr, net::TcpStream};
-
-use tokio_modbus::{
-    client::{tcp, Context},
-    Slave,
+use std::{
+    fmt,
+    net::{SocketAddr, ToSocketAddrs},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use byteorder::{BigEndian, ByteOrder};
 
+mod csv;
+mod influx;
+pub mod metrics;
+mod power_flow;
+mod registers;
+mod values;
+
+#[cfg(feature = "control")]
+pub mod control;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+#[cfg(feature = "units")]
+pub mod units;
+
+pub use csv::CsvLogger;
+pub use power_flow::PowerFlow;
+pub use registers::{handled_registers, RegisterInfo};
+
 pub struct GxDevice {
     socket_addr: SocketAddr,
+    read_only: bool,
+    requests: AtomicU64,
+    errors: AtomicU64,
+    last_setpoint: Mutex<Option<f32>>,
+    soh_history: Mutex<Option<(f32, f32)>>,
+    last_read: Mutex<Option<Instant>>,
+    connect_attempts: u32,
+    vebus_device: u8,
+    export_precision: Option<usize>,
+}
+
+/// Counters of the Modbus requests made by a [`GxDevice`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    /// Register reads and writes attempted.
+    pub requests: u64,
+    /// Requests the GX answered with an error.
+    pub errors: u64,
+}
+
+/// What a setter wrote to the GX, for audit logging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteReceipt {
+    pub device_id: u8,
+    pub address: u16,
+    /// Raw register value, after scaling.
+    pub value: u16,
+}
+
+/// Level of a GX alarm register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmLevel {
+    Ok,
+    Warning,
+    Alarm,
+    Unknown(u16),
+}
+
+impl From<u16> for AlarmLevel {
+    fn from(code: u16) -> Self {
+        match code {
+            0 => AlarmLevel::Ok,
+            1 => AlarmLevel::Warning,
+            2 => AlarmLevel::Alarm,
+            code => AlarmLevel::Unknown(code),
+        }
+    }
+}
+
+/// Type of the source connected to the active AC input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcSource {
+    Grid,
+    Generator,
+    Shore,
+    NotConnected,
+    Unknown,
+}
+
+impl From<u16> for AcSource {
+    fn from(code: u16) -> Self {
+        match code {
+            1 => AcSource::Grid,
+            2 => AcSource::Generator,
+            3 => AcSource::Shore,
+            240 => AcSource::NotConnected,
+            _ => AcSource::Unknown,
+        }
+    }
+}
+
+/// Error reported by the VE.Bus system, as its numeric code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VeBusError {
+    pub code: u16,
+}
+
+impl VeBusError {
+    /// Whether the code means "no error".
+    pub fn is_ok(&self) -> bool {
+        self.code == 0
+    }
+
+    /// Description of the code, as documented by Victron.
+    pub fn description(&self) -> &'static str {
+        match self.code {
+            0 => "No error",
+            1 => "Switched off because another phase in the system switched off",
+            2 => "New and old type MK2 are mixed in the system",
+            3 => "Not all, or more than, the expected devices were found in the system",
+            4 => "No other device detected",
+            5 => "Overvoltage on AC-out",
+            6 => "Error in DDC program",
+            7 => "VE.Bus BMS connected but no assistant found",
+            8 => "Ground relay test failed",
+            10 => "System time synchronisation problem",
+            11 => "Relay test fault",
+            12 => "Config mismatch with the second MCU",
+            14 => "Device cannot transmit data",
+            15 => "VE.Bus combination error",
+            16 => "Dongle missing",
+            17 => "A device assumed master status because the original master failed",
+            18 => "AC overvoltage on the output of a slave that was already switched off",
+            22 => "Device cannot function as slave",
+            24 => "Switch-over system protection initiated",
+            25 => "Firmware incompatibility",
+            26 => "Internal error",
+            _ => "Unknown VE.Bus error",
+        }
+    }
+}
+
+impl From<u16> for VeBusError {
+    fn from(code: u16) -> Self {
+        VeBusError { code }
+    }
+}
+
+impl fmt::Display for VeBusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VE.Bus error {}: {}", self.code, self.description())
+    }
+}
+
+/// Position of the VE.Bus remote switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchPosition {
+    ChargerOnly,
+    InverterOnly,
+    On,
+    Off,
+}
+
+impl From<SwitchPosition> for u16 {
+    fn from(position: SwitchPosition) -> Self {
+        match position {
+            SwitchPosition::ChargerOnly => 1,
+            SwitchPosition::InverterOnly => 2,
+            SwitchPosition::On => 3,
+            SwitchPosition::Off => 4,
+        }
+    }
+}
+
+impl FromStr for SwitchPosition {
+    type Err = anyhow::Error;
+
+    /// Parses `"on"`, `"off"`, `"charger"` or `"inverter"`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "on" => Ok(SwitchPosition::On),
+            "off" => Ok(SwitchPosition::Off),
+            "charger" => Ok(SwitchPosition::ChargerOnly),
+            "inverter" => Ok(SwitchPosition::InverterOnly),
+            _ => Err(anyhow::Error::msg(format!("Unknown switch position: {s}"))),
+        }
+    }
+}
+
+/// Validates the `VICTRON_HOST` and `VICTRON_PORT` values used by
+/// [`GxDevice::from_env`].
+fn host_and_port(
+    host: Option<String>,
+    port: Option<String>,
+) -> Result<(String, u16), anyhow::Error> {
+    let host = host.ok_or_else(|| anyhow::Error::msg("VICTRON_HOST is not set"))?;
+    let port = match port {
+        Some(port) => port
+            .parse::<u16>()
+            .map_err(|_| anyhow::Error::msg(format!("Invalid VICTRON_PORT: {port}")))?,
+        None => 502,
+    };
+    Ok((host, port))
+}
+
+/// Whether all `alarms` and the VE.Bus error were read, no alarm is raised or
+/// of unknown level, and the VE.Bus reports no error.
+fn alarms_healthy(
+    alarms: &[Result<AlarmLevel, anyhow::Error>],
+    vebus_error: &Result<VeBusError, anyhow::Error>,
+) -> bool {
+    matches!(vebus_error, Ok(error) if error.is_ok())
+        && alarms
+            .iter()
+            .all(|alarm| matches!(alarm, Ok(AlarmLevel::Ok | AlarmLevel::Warning)))
+}
+
+/// Interprets a register as a two's complement signed value.
+fn register_i16(raw: u16) -> i16 {
+    BigEndian::read_i16(&raw.to_be_bytes())
+}
+
+/// Interprets a 0/1 flag register; any non-zero value is set.
+fn register_bool(raw: u16) -> bool {
+    raw != 0
+}
+
+/// Joins four registers, most significant first, into a 64-bit value.
+fn registers_u64(registers: &[u16]) -> u64 {
+    let bytes: Vec<u8> = registers.iter().flat_map(|reg| reg.to_be_bytes()).collect();
+    BigEndian::read_u64(&bytes)
+}
+
+/// Decodes two ASCII characters per register, dropping trailing NULs.
+fn registers_string(registers: &[u16]) -> String {
+    let bytes: Vec<u8> = registers.iter().flat_map(|reg| reg.to_be_bytes()).collect();
+    String::from_utf8_lossy(&bytes)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+/// Formats an exported value, rounded to `precision` decimal places if set.
+fn format_value(value: f32, precision: Option<usize>) -> String {
+    match precision {
+        Some(decimals) => format!("{:.*}", decimals, value),
+        None => value.to_string(),
+    }
+}
+
+/// Scales `value` to a raw register value, checking it fits in `min..=max`
+/// instead of letting the cast saturate.
+fn scale_to_raw(value: f32, scale_factor: f32, min: f32, max: f32) -> Result<f32, anyhow::Error> {
+    let raw = (value * scale_factor).round();
+    if !(min..=max).contains(&raw) {
+        return Err(anyhow::Error::msg(format!(
+            "{} doesn't fit in the register (raw value {} outside {}..={})",
+            value, raw, min, max
+        )));
+    }
+    Ok(raw)
 }
 
 const GX_VE_DEVICE_0: u8 = 100;
@@ -17,31 +271,203 @@ const GX_CAN_BUS_BMS: u8 = 225;
 
 impl GxDevice {
     pub fn new(socket_addr: SocketAddr) -> Self {
-        GxDevice { socket_addr }
+        GxDevice {
+            socket_addr,
+            read_only: false,
+            requests: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            last_setpoint: Mutex::new(None),
+            soh_history: Mutex::new(None),
+            last_read: Mutex::new(None),
+            connect_attempts: 3,
+            vebus_device: GX_MII_VE_BUS,
+            export_precision: None,
+        }
     }
 
-    pub async fn get_modbus_i16(
+    /// Builds a `GxDevice` from the `VICTRON_HOST` (required, IP address or
+    /// host name) and `VICTRON_PORT` (defaults to 502) environment variables.
+    pub fn from_env() -> Result<Self, anyhow::Error> {
+        let (host, port) = host_and_port(
+            std::env::var("VICTRON_HOST").ok(),
+            std::env::var("VICTRON_PORT").ok(),
+        )?;
+
+        let socket_addr = (host.as_str(), port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow::Error::msg(format!("Could not resolve {host}")))?;
+        Ok(GxDevice::new(socket_addr))
+    }
+
+    /// Sets the unit id of the VE.Bus system read by the VE.Bus getters.
+    ///
+    /// Sites with several VE.Bus systems use one `GxDevice` per system, each
+    /// with the unit id listed for it in the GX's Modbus TCP services.
+    pub fn with_vebus_device(mut self, device_id: u8) -> Self {
+        self.vebus_device = device_id;
+        self
+    }
+
+    pub fn stats(&self) -> ClientStats {
+        ClientStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+
+    /// When set, every write is refused with an error before anything is sent
+    /// to the GX. Reads are unaffected.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Number of times a Modbus-TCP connection is attempted before a request
+    /// fails, waiting 100 ms, then 200 ms, and so on between attempts. Defaults
+    /// to 3; `1` disables retrying.
+    ///
+    /// Every getter and setter opens its own connection, so with the default
+    /// each one takes up to 300 ms longer to fail against an unreachable GX.
+    /// Calls made of many reads, such as [`GxDevice::values`] or
+    /// [`GxDevice::is_healthy`], add that delay up per read.
+    pub fn with_connect_attempts(mut self, attempts: u32) -> Self {
+        self.connect_attempts = attempts.max(1);
+        self
+    }
+
+    /// Rounds the values in the exported formats (Prometheus, InfluxDB, JSON
+    /// and CSV) to `decimals` decimal places. Getters are unaffected.
+    pub fn with_export_precision(mut self, decimals: usize) -> Self {
+        self.export_precision = Some(decimals);
+        self
+    }
+
+    fn check_writable(&self) -> Result<(), anyhow::Error> {
+        if self.read_only {
+            return Err(anyhow::Error::msg("GxDevice is in read-only mode"));
+        }
+        Ok(())
+    }
+
+    /// Time elapsed since the last successful register read, whatever the
+    /// register, or `None` before the first one. Useful as a liveness signal
+    /// for watchdogs.
+    pub fn last_message_age(&self) -> Option<Duration> {
+        self.last_read.lock().unwrap().map(|at| at.elapsed())
+    }
+
+    /// Whether the GX currently accepts Modbus-TCP connections. There is no
+    /// long-lived link to monitor: every reading opens its own connection.
+    pub async fn is_reachable(&self) -> bool {
+        use tokio_modbus::prelude::*;
+
+        match tcp::connect_slave(self.socket_addr, Slave(GX_VE_DEVICE_0)).await {
+            Ok(mut ctx) => {
+                let _ = ctx.disconnect().await;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Opens a Modbus-TCP connection to `device_id` on the GX, for registers
+    /// this crate doesn't model.
+    ///
+    /// Writes issued through the returned context bypass the read-only mode
+    /// and any validation done by the setters.
+    pub async fn connect(
+        &self,
+        device_id: u8,
+    ) -> Result<tokio_modbus::client::Context, anyhow::Error> {
+        use tokio_modbus::prelude::*;
+
+        let mut backoff = Duration::from_millis(100);
+        for _ in 1..self.connect_attempts {
+            if let Ok(ctx) = tcp::connect_slave(self.socket_addr, Slave(device_id)).await {
+                return Ok(ctx);
+            }
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+
+        Ok(tcp::connect_slave(self.socket_addr, Slave(device_id)).await?)
+    }
+
+    /// Reads `count` holding registers. Connection failures are returned as
+    /// errors rather than panicking, so a polling loop survives a GX that is
+    /// briefly unreachable.
+    async fn read_registers(
         &self,
         device_id: u8,
         address: u16,
-        scale_factor: f32,
-    ) -> Result<f32, anyhow::Error> {
+        count: u16,
+    ) -> Result<Vec<u16>, anyhow::Error> {
         use tokio_modbus::prelude::*;
 
-        let slave = Slave(device_id);
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let mut ctx = match self.connect(device_id).await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
+
+        let x = ctx.read_holding_registers(address, count).await;
+        let _ = ctx.disconnect().await;
 
-        let mut ctx = tcp::connect_slave(self.socket_addr, slave).await.unwrap();
+        if let Ok(Ok(x)) = x {
+            *self.last_read.lock().unwrap() = Some(Instant::now());
+            return Ok(x);
+        }
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        Err(anyhow::Error::msg("Error reading modbus"))
+    }
+
+    /// Writes a single holding register, see [`GxDevice::read_registers`].
+    async fn write_register(
+        &self,
+        device_id: u8,
+        address: u16,
+        raw_val: u16,
+    ) -> Result<WriteReceipt, anyhow::Error> {
+        use tokio_modbus::prelude::*;
 
-        let x = ctx.read_holding_registers(address, 1).await.unwrap();
+        self.check_writable()?;
 
-        if let Ok(x) = x {
-            ctx.disconnect().await.unwrap();
-            let val = x[0];
-            let real_val = BigEndian::read_i16(&val.to_be_bytes());
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let mut ctx = match self.connect(device_id).await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        };
 
-            return Ok(real_val as f32 / scale_factor);
+        let x = ctx.write_single_register(address, raw_val).await;
+        let _ = ctx.disconnect().await;
+
+        if let Ok(Ok(())) = x {
+            return Ok(WriteReceipt {
+                device_id,
+                address,
+                value: raw_val,
+            });
         }
-        return Err(anyhow::Error::msg("Error reading modbus"));
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        Err(anyhow::Error::msg("Error writing modbus"))
+    }
+
+    pub async fn get_modbus_i16(
+        &self,
+        device_id: u8,
+        address: u16,
+        scale_factor: f32,
+    ) -> Result<f32, anyhow::Error> {
+        let x = self.read_registers(device_id, address, 1).await?;
+
+        Ok(register_i16(x[0]) as f32 / scale_factor)
     }
 
     pub async fn get_modbus_u16(
@@ -50,29 +476,96 @@ impl GxDevice {
         address: u16,
         scale_factor: f32,
     ) -> Result<f32, anyhow::Error> {
-        use tokio_modbus::prelude::*;
+        let x = self.read_registers(device_id, address, 1).await?;
+
+        Ok(x[0] as f32 / scale_factor)
+    }
+
+    /// Reads a register without scaling, for codes and enumerations.
+    pub async fn get_modbus_raw_u16(
+        &self,
+        device_id: u8,
+        address: u16,
+    ) -> Result<u16, anyhow::Error> {
+        Ok(self.read_registers(device_id, address, 1).await?[0])
+    }
 
-        let slave = Slave(device_id);
+    /// Reads a 0/1 flag register.
+    pub async fn get_modbus_bool(
+        &self,
+        device_id: u8,
+        address: u16,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(register_bool(
+            self.get_modbus_raw_u16(device_id, address).await?,
+        ))
+    }
 
-        let mut ctx = tcp::connect_slave(self.socket_addr, slave).await.unwrap();
+    /// Reads a string spread over `count` registers, two ASCII characters per
+    /// register, with trailing NULs removed.
+    pub async fn get_modbus_string(
+        &self,
+        device_id: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<String, anyhow::Error> {
+        let x = self.read_registers(device_id, address, count).await?;
 
-        let x = ctx.read_holding_registers(address, 1).await.unwrap();
+        Ok(registers_string(&x))
+    }
 
-        if let Ok(x) = x {
-            ctx.disconnect().await.unwrap();
-            return Ok(x[0] as f32 / scale_factor);
-        }
-        return Err(anyhow::Error::msg("Error reading modbus"));
+    /// Reads an unsigned 64-bit value stored big-endian in four registers.
+    pub async fn get_modbus_u64(&self, device_id: u8, address: u16) -> Result<u64, anyhow::Error> {
+        let x = self.read_registers(device_id, address, 4).await?;
+
+        Ok(registers_u64(&x))
+    }
+
+    pub async fn set_modbus_u16(
+        &self,
+        device_id: u8,
+        address: u16,
+        value: f32,
+        scale_factor: f32,
+    ) -> Result<WriteReceipt, anyhow::Error> {
+        let raw_val = scale_to_raw(value, scale_factor, u16::MIN.into(), u16::MAX.into())? as u16;
+        self.write_register(device_id, address, raw_val).await
+    }
+
+    pub async fn set_modbus_i16(
+        &self,
+        device_id: u8,
+        address: u16,
+        value: f32,
+        scale_factor: f32,
+    ) -> Result<WriteReceipt, anyhow::Error> {
+        let raw_val = scale_to_raw(value, scale_factor, i16::MIN.into(), i16::MAX.into())? as i16;
+        self.write_register(device_id, address, raw_val as u16)
+            .await
+    }
+
+    /// Writes a 0/1 flag register.
+    pub async fn set_modbus_bool(
+        &self,
+        device_id: u8,
+        address: u16,
+        value: bool,
+    ) -> Result<WriteReceipt, anyhow::Error> {
+        self.set_modbus_u16(device_id, address, u8::from(value) as f32, 1.)
+            .await
     }
 
     pub async fn get_input_power(&self) -> Result<f32, anyhow::Error> {
-        self.get_modbus_i16(GX_MII_VE_BUS, 12, 0.1).await
+        self.get_modbus_i16(self.vebus_device, 12, 0.1).await
     }
 
     pub async fn get_output_power(&self) -> Result<f32, anyhow::Error> {
-        self.get_modbus_i16(GX_MII_VE_BUS, 23, 0.1).await
+        self.get_modbus_i16(self.vebus_device, 23, 0.1).await
     }
 
+    /// Battery power, in watts. Positive when the battery is charging and
+    /// negative when it is discharging; all battery readings of this crate
+    /// (power and current) follow this convention.
     pub async fn get_battery_power(&self) -> Result<f32, anyhow::Error> {
         self.get_modbus_i16(GX_CAN_BUS_BMS, 258, 1.).await
     }
@@ -88,4 +581,829 @@ impl GxDevice {
     pub async fn get_setpoint(&self) -> Result<f32, anyhow::Error> {
         self.get_modbus_i16(GX_VE_DEVICE_0, 2700, 1.).await
     }
+
+    /// Sets the ESS grid setpoint, in watts. Positive values import from the
+    /// grid, negative values export to it.
+    pub async fn set_setpoint(&self, watts: f32) -> Result<WriteReceipt, anyhow::Error> {
+        let receipt = self.set_modbus_i16(GX_VE_DEVICE_0, 2700, watts, 1.).await?;
+        *self.last_setpoint.lock().unwrap() = Some(watts);
+        Ok(receipt)
+    }
+
+    /// Difference between the last setpoint written with
+    /// [`GxDevice::set_setpoint`] and the actual grid power, in watts. Tends to
+    /// zero once the VE.Bus has finished ramping.
+    ///
+    /// `None` if no setpoint was written through this `GxDevice`.
+    pub async fn setpoint_ramp_error(&self) -> Result<Option<f32>, anyhow::Error> {
+        let commanded = *self.last_setpoint.lock().unwrap();
+        match commanded {
+            Some(commanded) => Ok(Some(commanded - self.get_grid_power().await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// ESS minimum SOC (unless grid fails), in percent.
+    pub async fn get_min_soc_limit(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_VE_DEVICE_0, 2901, 10.).await
+    }
+
+    pub async fn set_min_soc_limit(&self, percent: f32) -> Result<WriteReceipt, anyhow::Error> {
+        if !(0. ..=100.).contains(&percent) {
+            return Err(anyhow::Error::msg(
+                "Minimum SOC limit must be between 0 and 100 %",
+            ));
+        }
+        self.set_modbus_u16(GX_VE_DEVICE_0, 2901, percent, 10.)
+            .await
+    }
+
+    /// ESS maximum inverter (discharge) power, in watts.
+    pub async fn get_max_discharge_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_VE_DEVICE_0, 2704, 0.1).await
+    }
+
+    pub async fn set_max_discharge_power(&self, watts: f32) -> Result<WriteReceipt, anyhow::Error> {
+        if watts < 0. {
+            return Err(anyhow::Error::msg(
+                "Maximum discharge power can't be negative",
+            ));
+        }
+        self.set_modbus_u16(GX_VE_DEVICE_0, 2704, watts, 0.1).await
+    }
+
+    /// DVCC maximum charge current, in amps. Over Modbus, this is the way to cap
+    /// the charge rate of an ESS system.
+    ///
+    /// `-1` means no limit is set, see
+    /// [`GxDevice::clear_max_charge_current`].
+    pub async fn get_max_charge_current(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(GX_VE_DEVICE_0, 2705, 1.).await
+    }
+
+    pub async fn set_max_charge_current(&self, amps: f32) -> Result<WriteReceipt, anyhow::Error> {
+        if amps < 0. {
+            return Err(anyhow::Error::msg(
+                "Maximum charge current can't be negative",
+            ));
+        }
+        self.set_modbus_i16(GX_VE_DEVICE_0, 2705, amps, 1.).await
+    }
+
+    /// Removes the DVCC maximum charge current, by writing the `-1` "no limit"
+    /// value.
+    pub async fn clear_max_charge_current(&self) -> Result<WriteReceipt, anyhow::Error> {
+        self.set_modbus_i16(GX_VE_DEVICE_0, 2705, -1., 1.).await
+    }
+
+    /// VE.Bus AC output frequency, in Hz.
+    pub async fn get_output_frequency(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(self.vebus_device, 21, 100.).await
+    }
+
+    /// L1 power of a PV inverter, in watts. `device_id` is the unit id listed
+    /// for the PV inverter in the GX's Modbus TCP services.
+    pub async fn get_pv_inverter_power(&self, device_id: u8) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(device_id, 1028, 1.).await
+    }
+
+    /// L1 voltage of a PV inverter, in volts.
+    pub async fn get_pv_inverter_voltage(&self, device_id: u8) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(device_id, 1026, 10.).await
+    }
+
+    /// Grid power summed over all phases, in watts. Positive when importing.
+    /// Phases that can't be read (single-phase systems) are skipped.
+    pub async fn get_grid_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_system_phases_sum(820, true).await
+    }
+
+    /// Sums a per-phase system service power, stored as L1/L2/L3 in three
+    /// consecutive registers starting at `address`. L1 must be readable;
+    /// L2 and L3 are skipped when they can't be read.
+    async fn get_system_phases_sum(
+        &self,
+        address: u16,
+        signed: bool,
+    ) -> Result<f32, anyhow::Error> {
+        let mut total = 0.;
+        for phase_address in address..address + 3 {
+            let phase = if signed {
+                self.get_modbus_i16(GX_VE_DEVICE_0, phase_address, 1.).await
+            } else {
+                self.get_modbus_u16(GX_VE_DEVICE_0, phase_address, 1.).await
+            };
+            match phase {
+                Ok(phase) => total += phase,
+                Err(e) if phase_address == address => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Ok(total)
+    }
+
+    /// VE.Bus active AC input current limit, in amps.
+    pub async fn get_input_current_limit(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(self.vebus_device, 22, 10.).await
+    }
+
+    pub async fn set_input_current_limit(&self, amps: f32) -> Result<WriteReceipt, anyhow::Error> {
+        if !(0. ..=100.).contains(&amps) {
+            return Err(anyhow::Error::msg(
+                "Input current limit must be between 0 and 100 A",
+            ));
+        }
+        self.set_modbus_i16(self.vebus_device, 22, amps, 10.).await
+    }
+
+    /// VE.Bus AC output voltage (L1), in volts.
+    pub async fn get_output_voltage(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(self.vebus_device, 15, 10.).await
+    }
+
+    /// VE.Bus AC output current (L1), in amps. The GX doesn't publish the load
+    /// as a percentage over Modbus; compare this against the inverter rating.
+    pub async fn get_output_current(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(self.vebus_device, 18, 10.).await
+    }
+
+    /// VE.Bus AC output apparent power (L1), in VA, from voltage × current.
+    pub async fn get_output_apparent_power(&self) -> Result<f32, anyhow::Error> {
+        let voltage = self.get_output_voltage().await?;
+        let current = self.get_output_current().await?;
+        Ok(voltage * current)
+    }
+
+    /// Voltage of the main battery (DC input 0), in volts.
+    pub async fn get_battery_voltage(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_CAN_BUS_BMS, 259, 100.).await
+    }
+
+    /// Current of the main battery (DC input 0), in amps. Positive when
+    /// charging.
+    pub async fn get_battery_current(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(GX_CAN_BUS_BMS, 261, 10.).await
+    }
+
+    /// Voltage of the secondary DC input (starter battery), in volts. Only
+    /// available on monitors that have one, such as a BMV.
+    pub async fn get_starter_battery_voltage(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_CAN_BUS_BMS, 260, 100.).await
+    }
+
+    /// Serial number of the GX (its VRM portal id).
+    pub async fn get_serial(&self) -> Result<String, anyhow::Error> {
+        self.get_modbus_string(GX_VE_DEVICE_0, 800, 6).await
+    }
+
+    /// VE.Bus AC input voltage (L1), in volts.
+    pub async fn get_input_voltage(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(self.vebus_device, 3, 10.).await
+    }
+
+    /// VE.Bus AC input current (L1), in amps.
+    pub async fn get_input_current(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(self.vebus_device, 6, 10.).await
+    }
+
+    /// VE.Bus AC input apparent power (L1), in VA, from voltage × current.
+    pub async fn get_input_apparent_power(&self) -> Result<f32, anyhow::Error> {
+        let voltage = self.get_input_voltage().await?;
+        let current = self.get_input_current().await?;
+        Ok(voltage * current)
+    }
+
+    /// DC-coupled PV power (solar chargers), in watts.
+    pub async fn get_dc_pv_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_VE_DEVICE_0, 850, 1.).await
+    }
+
+    /// AC-coupled PV power on the AC output, summed over all phases, in watts.
+    pub async fn get_ac_pv_on_output_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_system_phases_sum(808, false).await
+    }
+
+    /// AC-coupled PV power on the AC input (grid side), summed over all
+    /// phases, in watts.
+    pub async fn get_ac_pv_on_grid_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_system_phases_sum(811, false).await
+    }
+
+    /// AC-coupled PV power on the generator input, summed over all phases, in
+    /// watts.
+    pub async fn get_ac_pv_on_genset_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_system_phases_sum(814, false).await
+    }
+
+    /// AC-coupled PV power of all placements (output, grid and generator)
+    /// combined, in watts. Fails only if none of them can be read.
+    pub async fn get_ac_pv_power(&self) -> Result<f32, anyhow::Error> {
+        let placements = [
+            self.get_ac_pv_on_output_power().await,
+            self.get_ac_pv_on_grid_power().await,
+            self.get_ac_pv_on_genset_power().await,
+        ];
+
+        let mut total = Err(anyhow::Error::msg("No AC-coupled PV placement"));
+        for placement in placements {
+            total = match (total, placement) {
+                (Ok(total), Ok(power)) => Ok(total + power),
+                (Ok(total), Err(_)) => Ok(total),
+                (Err(_), placement) => placement,
+            };
+        }
+        total
+    }
+
+    /// DC- and AC-coupled PV power combined, in watts. Either source may be
+    /// missing, depending on how the PV is connected; fails only if both are.
+    pub async fn get_total_pv_power(&self) -> Result<f32, anyhow::Error> {
+        let dc = self.get_dc_pv_power().await;
+        let ac = self.get_ac_pv_power().await;
+        match (dc, ac) {
+            (Err(e), Err(_)) => Err(e),
+            (dc, ac) => Ok(dc.unwrap_or(0.) + ac.unwrap_or(0.)),
+        }
+    }
+
+    /// Undoes [`GxDevice::set_setpoint`] by writing back `default_watts` as
+    /// the ESS grid setpoint, typically 0 W (the GX default) or whatever the
+    /// site had configured before. The ESS mode is left untouched.
+    pub async fn ess_release(&self, default_watts: f32) -> Result<WriteReceipt, anyhow::Error> {
+        self.set_setpoint(default_watts).await
+    }
+
+    pub async fn is_battery_charging(&self) -> Result<bool, anyhow::Error> {
+        Ok(self.get_battery_power().await? > 0.)
+    }
+
+    pub async fn is_battery_discharging(&self) -> Result<bool, anyhow::Error> {
+        Ok(self.get_battery_power().await? < 0.)
+    }
+
+    /// Today's yield of a solar charger, in kWh. `device_id` is the unit id
+    /// listed for the solar charger in the GX's Modbus TCP services.
+    pub async fn get_solar_charger_yield_today(&self, device_id: u8) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(device_id, 784, 10.).await
+    }
+
+    /// Yesterday's yield of a solar charger, in kWh.
+    pub async fn get_solar_charger_yield_yesterday(
+        &self,
+        device_id: u8,
+    ) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(device_id, 786, 10.).await
+    }
+
+    /// AC consumption (house load) summed over all phases, in watts. Unlike
+    /// the VE.Bus output power, this includes loads fed by AC-coupled PV.
+    pub async fn get_consumption_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_system_phases_sum(817, false).await
+    }
+
+    /// AC consumption of a single phase (1 to 3), in watts.
+    pub async fn get_phase_consumption_power(&self, phase: u8) -> Result<f32, anyhow::Error> {
+        if !(1..=3).contains(&phase) {
+            return Err(anyhow::Error::msg("Phase must be between 1 and 3"));
+        }
+        self.get_modbus_u16(GX_VE_DEVICE_0, 816 + phase as u16, 1.)
+            .await
+    }
+
+    /// VE.Bus grid lost alarm, raised when the AC input disappears.
+    pub async fn get_grid_lost_alarm(&self) -> Result<AlarmLevel, anyhow::Error> {
+        Ok(self.get_modbus_raw_u16(self.vebus_device, 64).await?.into())
+    }
+
+    /// Battery voltage as seen by the system service, in volts. Unlike the
+    /// battery getters, these system-level readings are also available on
+    /// installations without a VE.Bus device or a CAN-bus BMS.
+    pub async fn get_system_battery_voltage(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_VE_DEVICE_0, 840, 10.).await
+    }
+
+    /// Battery current as seen by the system service, in amps. Positive when
+    /// charging.
+    pub async fn get_system_battery_current(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(GX_VE_DEVICE_0, 841, 10.).await
+    }
+
+    /// Battery power as seen by the system service, in watts. Positive when
+    /// charging.
+    pub async fn get_system_battery_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(GX_VE_DEVICE_0, 842, 1.).await
+    }
+
+    /// Whether excess DC-coupled PV is fed into the grid.
+    pub async fn get_feed_in_excess_dc_pv(&self) -> Result<bool, anyhow::Error> {
+        self.get_modbus_bool(GX_VE_DEVICE_0, 2707).await
+    }
+
+    pub async fn set_feed_in_excess_dc_pv(
+        &self,
+        enabled: bool,
+    ) -> Result<WriteReceipt, anyhow::Error> {
+        self.set_modbus_bool(GX_VE_DEVICE_0, 2707, enabled).await
+    }
+
+    /// Whether excess AC-coupled PV is fed into the grid. The GX stores the
+    /// inverse ("prevent feedback"), which is translated here.
+    pub async fn get_feed_in_excess_ac_pv(&self) -> Result<bool, anyhow::Error> {
+        Ok(!self.get_modbus_bool(GX_VE_DEVICE_0, 2708).await?)
+    }
+
+    pub async fn set_feed_in_excess_ac_pv(
+        &self,
+        enabled: bool,
+    ) -> Result<WriteReceipt, anyhow::Error> {
+        self.set_modbus_bool(GX_VE_DEVICE_0, 2708, !enabled).await
+    }
+
+    /// Battery power in watts, falling back to voltage × current for BMSes
+    /// that don't report the power itself.
+    pub async fn get_battery_effective_power(&self) -> Result<f32, anyhow::Error> {
+        if let Ok(power) = self.get_battery_power().await {
+            return Ok(power);
+        }
+
+        let voltage = self.get_battery_voltage().await?;
+        let current = self.get_battery_current().await?;
+        Ok(voltage * current)
+    }
+
+    /// Type of the source on the active AC input, as configured on the GX.
+    pub async fn get_active_input_source(&self) -> Result<AcSource, anyhow::Error> {
+        Ok(self.get_modbus_raw_u16(GX_VE_DEVICE_0, 826).await?.into())
+    }
+
+    /// Charge voltage limit (DVL) requested by the BMS, in volts.
+    pub async fn get_battery_max_charge_voltage(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_CAN_BUS_BMS, 305, 10.).await
+    }
+
+    /// Charge current limit (CCL) requested by the BMS, in amps.
+    pub async fn get_battery_max_charge_current(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_CAN_BUS_BMS, 307, 10.).await
+    }
+
+    /// Discharge current limit (DCL) requested by the BMS, in amps.
+    pub async fn get_battery_max_discharge_current(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_CAN_BUS_BMS, 308, 10.).await
+    }
+
+    /// VE.Bus high temperature alarm of the inverter/charger itself.
+    pub async fn get_temperature_alarm(&self) -> Result<AlarmLevel, anyhow::Error> {
+        Ok(self.get_modbus_raw_u16(self.vebus_device, 34).await?.into())
+    }
+
+    /// Whether ESS is in "keep batteries charged" mode.
+    pub async fn get_keep_batteries_charged(&self) -> Result<bool, anyhow::Error> {
+        Ok(self.get_modbus_raw_u16(GX_VE_DEVICE_0, 2900).await? == 9)
+    }
+
+    /// Switches ESS to "keep batteries charged" mode.
+    pub async fn enable_keep_batteries_charged(&self) -> Result<WriteReceipt, anyhow::Error> {
+        self.set_modbus_u16(GX_VE_DEVICE_0, 2900, 9., 1.).await
+    }
+
+    /// Leaves "keep batteries charged" mode for "optimised with BatteryLife"
+    /// when `battery_life` is set, "optimised without BatteryLife" otherwise.
+    pub async fn disable_keep_batteries_charged(
+        &self,
+        battery_life: bool,
+    ) -> Result<WriteReceipt, anyhow::Error> {
+        let state = if battery_life { 1. } else { 10. };
+        self.set_modbus_u16(GX_VE_DEVICE_0, 2900, state, 1.).await
+    }
+
+    /// Current BatteryLife dynamic SOC limit, in percent: the SOC below which
+    /// ESS stops discharging the battery.
+    pub async fn get_battery_life_soc_limit(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_VE_DEVICE_0, 2903, 10.).await
+    }
+
+    /// Voltage of the lowest cell reported by the BMS, in volts.
+    pub async fn get_battery_min_cell_voltage(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_CAN_BUS_BMS, 1290, 100.).await
+    }
+
+    /// Voltage of the highest cell reported by the BMS, in volts.
+    pub async fn get_battery_max_cell_voltage(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_CAN_BUS_BMS, 1291, 100.).await
+    }
+
+    /// Battery state of health, in percent. The first and latest values read
+    /// are kept for [`GxDevice::soh_delta`].
+    pub async fn get_battery_soh(&self) -> Result<f32, anyhow::Error> {
+        let soh = self.get_modbus_u16(GX_CAN_BUS_BMS, 304, 10.).await?;
+        self.record_soh(soh);
+        Ok(soh)
+    }
+
+    /// Keeps the first SOH reading and replaces the latest one.
+    fn record_soh(&self, soh: f32) {
+        let mut history = self.soh_history.lock().unwrap();
+        let first = history.map_or(soh, |(first, _)| first);
+        *history = Some((first, soh));
+    }
+
+    /// Change of the state of health between the first and the latest
+    /// [`GxDevice::get_battery_soh`] reading, in percentage points. Negative
+    /// as the battery fades; `None` before the first reading.
+    ///
+    /// [`GxDevice::values`] reads the SOH too, so listing values also updates
+    /// the latest reading.
+    pub fn soh_delta(&self) -> Option<f32> {
+        self.soh_history
+            .lock()
+            .unwrap()
+            .map(|(first, latest)| latest - first)
+    }
+
+    /// VE.Bus low battery alarm.
+    pub async fn get_low_battery_alarm(&self) -> Result<AlarmLevel, anyhow::Error> {
+        Ok(self.get_modbus_raw_u16(self.vebus_device, 35).await?.into())
+    }
+
+    /// VE.Bus overload alarm, raised before the inverter shuts down on an
+    /// overloaded output.
+    pub async fn get_overload_alarm(&self) -> Result<AlarmLevel, anyhow::Error> {
+        Ok(self.get_modbus_raw_u16(self.vebus_device, 36).await?.into())
+    }
+
+    /// VE.Bus DC ripple alarm (L1). High ripple usually means undersized or
+    /// loose battery cables, or a failing capacitor. The ripple voltage itself
+    /// isn't available over Modbus.
+    pub async fn get_ripple_alarm(&self) -> Result<AlarmLevel, anyhow::Error> {
+        Ok(self.get_modbus_raw_u16(self.vebus_device, 47).await?.into())
+    }
+
+    /// Go/no-go summary: the VE.Bus reports no error and every VE.Bus alarm
+    /// (temperature, low battery, overload, ripple) reads as clear or
+    /// warning. Fails closed: a value that can't be read (GX unreachable,
+    /// wrong VE.Bus unit id) or an alarm of unknown level makes the system
+    /// unhealthy.
+    pub async fn is_healthy(&self) -> bool {
+        let alarms = [
+            self.get_temperature_alarm().await,
+            self.get_low_battery_alarm().await,
+            self.get_overload_alarm().await,
+            self.get_ripple_alarm().await,
+        ];
+        alarms_healthy(&alarms, &self.get_vebus_error().await)
+    }
+
+    /// Number of AC phases of the VE.Bus system.
+    pub async fn get_phase_count(&self) -> Result<u16, anyhow::Error> {
+        self.get_modbus_raw_u16(self.vebus_device, 28).await
+    }
+
+    /// Active VE.Bus AC input: `Some(1)` or `Some(2)`, `None` when no input is
+    /// connected.
+    pub async fn get_active_input(&self) -> Result<Option<u8>, anyhow::Error> {
+        match self.get_modbus_raw_u16(self.vebus_device, 29).await? {
+            0 => Ok(Some(1)),
+            1 => Ok(Some(2)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether the VE.Bus is raising its AC output frequency to throttle
+    /// AC-coupled PV, see [`metrics::is_frequency_shifting`].
+    pub async fn is_frequency_shifting(
+        &self,
+        nominal: f32,
+        threshold: f32,
+    ) -> Result<bool, anyhow::Error> {
+        let frequency = self.get_output_frequency().await?;
+        Ok(metrics::is_frequency_shifting(
+            frequency, nominal, threshold,
+        ))
+    }
+
+    /// Battery state of charge as seen by the system service, in percent.
+    /// Available without a managed BMS.
+    pub async fn get_system_battery_soc(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_u16(GX_VE_DEVICE_0, 843, 1.).await
+    }
+
+    /// Battery state of charge, in percent: the BMS value when it can be read,
+    /// the system service's value otherwise.
+    pub async fn get_battery_soc_with_fallback(&self) -> Result<f32, anyhow::Error> {
+        match self.get_battery_soc().await {
+            Ok(soc) => Ok(soc),
+            Err(_) => self.get_system_battery_soc().await,
+        }
+    }
+
+    /// Moves the VE.Bus remote switch.
+    pub async fn set_switch_position(
+        &self,
+        position: SwitchPosition,
+    ) -> Result<WriteReceipt, anyhow::Error> {
+        self.set_modbus_u16(self.vebus_device, 33, u16::from(position) as f32, 1.)
+            .await
+    }
+
+    /// Moves the VE.Bus remote switch to a position given by name: `"on"`,
+    /// `"off"`, `"charger"` or `"inverter"`, ignoring case.
+    pub async fn set_mode_str(&self, mode: &str) -> Result<WriteReceipt, anyhow::Error> {
+        self.set_switch_position(mode.parse()?).await
+    }
+
+    /// Power drawn by DC loads, as measured or computed by the GX, in watts.
+    pub async fn get_dc_system_power(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(GX_VE_DEVICE_0, 860, 1.).await
+    }
+
+    /// Grid power of a single phase (1 to 3), in watts, positive when
+    /// importing.
+    pub async fn get_phase_grid_power(&self, phase: u8) -> Result<f32, anyhow::Error> {
+        if !(1..=3).contains(&phase) {
+            return Err(anyhow::Error::msg("Phase must be between 1 and 3"));
+        }
+        self.get_modbus_i16(GX_VE_DEVICE_0, 819 + phase as u16, 1.)
+            .await
+    }
+
+    /// VE.Bus AC input current of a single phase (1 to 3), in amps.
+    pub async fn get_phase_input_current(&self, phase: u8) -> Result<f32, anyhow::Error> {
+        if !(1..=3).contains(&phase) {
+            return Err(anyhow::Error::msg("Phase must be between 1 and 3"));
+        }
+        self.get_modbus_i16(self.vebus_device, 5 + phase as u16, 10.)
+            .await
+    }
+
+    /// VE.Bus AC input frequency (L1), in hertz: the grid frequency while
+    /// connected to the grid.
+    pub async fn get_input_frequency(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(self.vebus_device, 9, 100.).await
+    }
+
+    /// Error currently reported by the VE.Bus system.
+    pub async fn get_vebus_error(&self) -> Result<VeBusError, anyhow::Error> {
+        Ok(self.get_modbus_raw_u16(self.vebus_device, 32).await?.into())
+    }
+
+    /// Current time of the GX clock. Compare it with the local clock to spot
+    /// a GX whose RTC has drifted or lost its time.
+    pub async fn get_system_time(&self) -> Result<SystemTime, anyhow::Error> {
+        let seconds = self.get_modbus_u64(GX_VE_DEVICE_0, 830).await?;
+        Ok(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    /// Whether power is currently flowing into the grid, see
+    /// [`metrics::is_exporting`].
+    pub async fn is_exporting(&self, deadband: f32) -> Result<bool, anyhow::Error> {
+        Ok(metrics::is_exporting(
+            self.get_grid_power().await?,
+            deadband,
+        ))
+    }
+
+    /// Battery temperature reported by the BMS, in °C.
+    pub async fn get_battery_temperature(&self) -> Result<f32, anyhow::Error> {
+        self.get_modbus_i16(GX_CAN_BUS_BMS, 262, 10.).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_known_vebus_errors() {
+        assert_eq!(
+            VeBusError::from(3).description(),
+            "Not all, or more than, the expected devices were found in the system"
+        );
+        assert_eq!(
+            VeBusError::from(8).to_string(),
+            "VE.Bus error 8: Ground relay test failed"
+        );
+        assert!(VeBusError::from(0).is_ok());
+    }
+
+    #[test]
+    fn unknown_vebus_errors_keep_their_code() {
+        let error = VeBusError::from(99);
+        assert_eq!(error.code, 99);
+        assert_eq!(error.description(), "Unknown VE.Bus error");
+    }
+
+    #[test]
+    fn clear_alarms_and_warnings_are_healthy() {
+        let alarms = [Ok(AlarmLevel::Ok), Ok(AlarmLevel::Warning)];
+        assert!(alarms_healthy(&alarms, &Ok(VeBusError::from(0))));
+    }
+
+    #[test]
+    fn raised_unknown_or_unreadable_alarms_are_unhealthy() {
+        let no_error = Ok(VeBusError::from(0));
+        assert!(!alarms_healthy(
+            &[Ok(AlarmLevel::Ok), Ok(AlarmLevel::Alarm)],
+            &no_error
+        ));
+        assert!(!alarms_healthy(&[Ok(AlarmLevel::Unknown(7))], &no_error));
+        assert!(!alarms_healthy(
+            &[Ok(AlarmLevel::Ok), Err(anyhow::Error::msg("timeout"))],
+            &no_error
+        ));
+    }
+
+    #[test]
+    fn vebus_errors_are_unhealthy() {
+        let alarms = [Ok(AlarmLevel::Ok)];
+        assert!(!alarms_healthy(&alarms, &Ok(VeBusError::from(17))));
+        assert!(!alarms_healthy(&alarms, &Ok(VeBusError::from(25))));
+        assert!(!alarms_healthy(
+            &alarms,
+            &Err(anyhow::Error::msg("timeout"))
+        ));
+    }
+
+    #[test]
+    fn scales_values_that_fit() {
+        assert_eq!(scale_to_raw(48.57, 100., 0., 65535.).unwrap(), 4857.);
+        assert_eq!(scale_to_raw(-2000., 1., -32768., 32767.).unwrap(), -2000.);
+    }
+
+    #[test]
+    fn rejects_values_that_would_saturate() {
+        assert!(scale_to_raw(-1., 1., 0., 65535.).is_err());
+        assert!(scale_to_raw(40000., 1., -32768., 32767.).is_err());
+        assert!(scale_to_raw(f32::NAN, 1., 0., 65535.).is_err());
+    }
+
+    #[test]
+    fn parses_switch_positions_ignoring_case() {
+        assert_eq!("on".parse::<SwitchPosition>().unwrap(), SwitchPosition::On);
+        assert_eq!(
+            "OFF".parse::<SwitchPosition>().unwrap(),
+            SwitchPosition::Off
+        );
+        assert_eq!(
+            "Charger".parse::<SwitchPosition>().unwrap(),
+            SwitchPosition::ChargerOnly
+        );
+        assert_eq!(
+            "inverter".parse::<SwitchPosition>().unwrap(),
+            SwitchPosition::InverterOnly
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_switch_positions() {
+        assert!("standby".parse::<SwitchPosition>().is_err());
+        assert!("".parse::<SwitchPosition>().is_err());
+    }
+
+    /// A device whose writes must fail before reaching the network.
+    fn offline_gx() -> GxDevice {
+        GxDevice::new("127.0.0.1:1".parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn read_only_mode_refuses_writes_before_sending_them() {
+        let gx = offline_gx().with_read_only(true);
+
+        assert!(gx.set_setpoint(0.).await.is_err());
+        assert!(gx
+            .set_modbus_bool(GX_VE_DEVICE_0, 2707, true)
+            .await
+            .is_err());
+        assert_eq!(gx.stats().requests, 0);
+    }
+
+    #[tokio::test]
+    async fn min_soc_limit_is_checked_before_writing() {
+        let gx = offline_gx();
+
+        assert!(gx.set_min_soc_limit(-1.).await.is_err());
+        assert!(gx.set_min_soc_limit(100.1).await.is_err());
+        assert_eq!(gx.stats().requests, 0);
+    }
+
+    #[tokio::test]
+    async fn negative_ess_limits_are_rejected_before_writing() {
+        let gx = offline_gx();
+
+        assert!(gx.set_max_discharge_power(-1.).await.is_err());
+        assert!(gx.set_max_charge_current(-0.5).await.is_err());
+        assert_eq!(gx.stats().requests, 0);
+    }
+
+    #[tokio::test]
+    async fn input_current_limit_is_checked_before_writing() {
+        let gx = offline_gx();
+
+        assert!(gx.set_input_current_limit(-1.).await.is_err());
+        assert!(gx.set_input_current_limit(100.5).await.is_err());
+        assert_eq!(gx.stats().requests, 0);
+    }
+
+    #[test]
+    fn env_port_defaults_to_502() {
+        assert_eq!(
+            host_and_port(Some("venus.local".into()), None).unwrap(),
+            ("venus.local".to_string(), 502)
+        );
+        assert_eq!(
+            host_and_port(Some("10.0.0.2".into()), Some("5020".into())).unwrap(),
+            ("10.0.0.2".to_string(), 5020)
+        );
+    }
+
+    #[test]
+    fn env_requires_a_host_and_a_valid_port() {
+        assert!(host_and_port(None, Some("502".into())).is_err());
+        assert!(host_and_port(Some("10.0.0.2".into()), Some("http".into())).is_err());
+        assert!(host_and_port(Some("10.0.0.2".into()), Some("70000".into())).is_err());
+    }
+
+    #[test]
+    fn maps_ac_source_codes() {
+        assert_eq!(AcSource::from(1), AcSource::Grid);
+        assert_eq!(AcSource::from(2), AcSource::Generator);
+        assert_eq!(AcSource::from(3), AcSource::Shore);
+        assert_eq!(AcSource::from(240), AcSource::NotConnected);
+        assert_eq!(AcSource::from(0), AcSource::Unknown);
+        assert_eq!(AcSource::from(99), AcSource::Unknown);
+    }
+
+    #[test]
+    fn maps_alarm_codes() {
+        assert_eq!(AlarmLevel::from(0), AlarmLevel::Ok);
+        assert_eq!(AlarmLevel::from(1), AlarmLevel::Warning);
+        assert_eq!(AlarmLevel::from(2), AlarmLevel::Alarm);
+        assert_eq!(AlarmLevel::from(5), AlarmLevel::Unknown(5));
+    }
+
+    #[test]
+    fn soh_delta_compares_first_and_latest_readings() {
+        let gx = offline_gx();
+        assert_eq!(gx.soh_delta(), None);
+
+        gx.record_soh(98.);
+        assert_eq!(gx.soh_delta(), Some(0.));
+        gx.record_soh(97.5);
+        gx.record_soh(96.);
+        assert_eq!(gx.soh_delta(), Some(-2.));
+    }
+
+    #[test]
+    fn decodes_signed_registers() {
+        assert_eq!(register_i16(0x0064), 100);
+        assert_eq!(register_i16(0xFFFF), -1);
+        assert_eq!(register_i16(0x8000), i16::MIN);
+    }
+
+    #[test]
+    fn decodes_flag_registers() {
+        assert!(!register_bool(0));
+        assert!(register_bool(1));
+        assert!(register_bool(2));
+    }
+
+    #[test]
+    fn decodes_multi_register_values() {
+        assert_eq!(
+            registers_u64(&[0x0000, 0x0000, 0x6543, 0x2100]),
+            0x6543_2100
+        );
+        assert_eq!(
+            registers_u64(&[0x0102, 0x0304, 0x0506, 0x0708]),
+            0x0102_0304_0506_0708
+        );
+        assert_eq!(registers_string(&[0x4843, 0x3132, 0x3300, 0x0000]), "HC123");
+    }
+
+    /// An address nothing listens on yet, for the connect retry tests.
+    fn free_addr() -> SocketAddr {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn connect_retries_until_the_gx_listens() {
+        let addr = free_addr();
+        let server = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+            listener.accept().await.unwrap();
+        });
+
+        let gx = GxDevice::new(addr).with_connect_attempts(3);
+        assert!(gx.connect(GX_VE_DEVICE_0).await.is_ok());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn connect_fails_without_retries() {
+        let gx = GxDevice::new(free_addr()).with_connect_attempts(1);
+        assert!(gx.connect(GX_VE_DEVICE_0).await.is_err());
+    }
 }
@@ -0,0 +1,211 @@
+//! Derived values computed from readings.
+//!
+//! All powers are in watts, with the grid positive when importing.
+
+use std::time::Duration;
+
+/// Share of the PV production that is used locally instead of exported, in
+/// percent.
+///
+/// `self_consumption = (pv - exported) / pv * 100`, where `exported` is the
+/// part of the grid power flowing out (`max(-grid, 0)`). The result is
+/// clamped to 0–100 %. Returns `None` when there is no PV production.
+pub fn self_consumption(pv_power: f32, grid_power: f32) -> Option<f32> {
+    if pv_power <= 0. {
+        return None;
+    }
+
+    let exported = (-grid_power).max(0.);
+    Some(((pv_power - exported) / pv_power * 100.).clamp(0., 100.))
+}
+
+/// Power factor from active power (W) and apparent power (VA).
+///
+/// Returns `None` when the apparent power is zero.
+pub fn power_factor(active_power: f32, apparent_power: f32) -> Option<f32> {
+    if apparent_power == 0. {
+        return None;
+    }
+
+    Some((active_power / apparent_power).abs().min(1.))
+}
+
+/// Constant charge power (W) bringing the battery from `soc` to `target_soc`
+/// (both in %) within `horizon`, given its usable `capacity_wh`.
+///
+/// Linear model: `(target_soc - soc) / 100 * capacity_wh / hours`. Charging
+/// losses and the slower absorption phase near full are ignored, so leave
+/// some margin in the horizon. Returns `Some(0.)` when the target is already
+/// reached, and `None` for a zero horizon or a non-positive capacity.
+pub fn recommended_charge_power(
+    soc: f32,
+    target_soc: f32,
+    capacity_wh: f32,
+    horizon: Duration,
+) -> Option<f32> {
+    let hours = horizon.as_secs_f32() / 3600.;
+    if hours <= 0. || capacity_wh <= 0. {
+        return None;
+    }
+
+    let missing_wh = (target_soc.min(100.) - soc) / 100. * capacity_wh;
+    Some(missing_wh.max(0.) / hours)
+}
+
+/// Default high cell voltage threshold for LiFePO4, in volts.
+pub const LIFEPO4_CELL_HIGH_VOLTAGE: f32 = 3.65;
+/// Default low cell voltage threshold for LiFePO4, in volts.
+pub const LIFEPO4_CELL_LOW_VOLTAGE: f32 = 2.8;
+
+/// Cell voltage outside of the configured window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellWarning {
+    /// The lowest cell is below the low threshold.
+    Low,
+    /// The highest cell is above the high threshold.
+    High,
+    /// Both at once, typically a badly unbalanced pack.
+    Both,
+}
+
+/// Checks the lowest and highest cell voltages against `low`/`high`
+/// thresholds, e.g. [`LIFEPO4_CELL_LOW_VOLTAGE`] and
+/// [`LIFEPO4_CELL_HIGH_VOLTAGE`]. Voltages equal to a threshold are fine.
+pub fn cell_warning(
+    min_cell_voltage: f32,
+    max_cell_voltage: f32,
+    low: f32,
+    high: f32,
+) -> Option<CellWarning> {
+    match (min_cell_voltage < low, max_cell_voltage > high) {
+        (true, true) => Some(CellWarning::Both),
+        (true, false) => Some(CellWarning::Low),
+        (false, true) => Some(CellWarning::High),
+        (false, false) => None,
+    }
+}
+
+/// Residual of the DC power balance, in watts:
+/// `battery_power - dc_pv_power + dc_system_power`, with the battery positive
+/// when charging.
+///
+/// On a DC bus where every source and load is metered this stays near zero;
+/// a persistent offset points at an unmetered DC load or a mis-scaled shunt.
+/// Returns `None` when any reading is missing.
+pub fn dc_balance_error(
+    battery_power: Option<f32>,
+    dc_pv_power: Option<f32>,
+    dc_system_power: Option<f32>,
+) -> Option<f32> {
+    Some(battery_power? - dc_pv_power? + dc_system_power?)
+}
+
+/// Whether power is flowing into the grid: true when `grid_power` is below
+/// `-deadband` watts, so metering noise around zero isn't reported as export.
+pub fn is_exporting(grid_power: f32, deadband: f32) -> bool {
+    grid_power < -deadband.abs()
+}
+
+/// Whether the AC output `frequency` exceeds `nominal` (50 or 60 Hz) by more
+/// than `threshold` Hz, i.e. the VE.Bus is frequency-shifting to throttle
+/// AC-coupled PV.
+pub fn is_frequency_shifting(frequency: f32, nominal: f32, threshold: f32) -> bool {
+    frequency - nominal > threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_consumption_counts_only_exported_pv() {
+        assert_eq!(self_consumption(4000., -1000.), Some(75.));
+        assert_eq!(self_consumption(4000., 500.), Some(100.));
+        assert_eq!(self_consumption(1000., -3000.), Some(0.));
+        assert_eq!(self_consumption(0., 500.), None);
+    }
+
+    #[test]
+    fn power_factor_is_bounded_and_unsigned() {
+        assert_eq!(power_factor(800., 1000.), Some(0.8));
+        assert_eq!(power_factor(-800., 1000.), Some(0.8));
+        assert_eq!(power_factor(1010., 1000.), Some(1.));
+        assert_eq!(power_factor(100., 0.), None);
+    }
+
+    #[test]
+    fn recommended_charge_power_spreads_missing_energy_over_horizon() {
+        let two_hours = Duration::from_secs(2 * 3600);
+        assert_eq!(
+            recommended_charge_power(50., 100., 10000., two_hours),
+            Some(2500.)
+        );
+        assert_eq!(
+            recommended_charge_power(90., 80., 10000., two_hours),
+            Some(0.)
+        );
+        assert_eq!(
+            recommended_charge_power(50., 120., 10000., two_hours),
+            Some(2500.)
+        );
+    }
+
+    #[test]
+    fn recommended_charge_power_rejects_degenerate_inputs() {
+        let hour = Duration::from_secs(3600);
+        assert_eq!(
+            recommended_charge_power(50., 100., 10000., Duration::ZERO),
+            None
+        );
+        assert_eq!(recommended_charge_power(50., 100., 0., hour), None);
+    }
+
+    #[test]
+    fn cell_warning_accepts_voltages_on_the_thresholds() {
+        let warning = |min, max| {
+            cell_warning(
+                min,
+                max,
+                LIFEPO4_CELL_LOW_VOLTAGE,
+                LIFEPO4_CELL_HIGH_VOLTAGE,
+            )
+        };
+        assert_eq!(warning(3.2, 3.4), None);
+        assert_eq!(warning(2.8, 3.65), None);
+        assert_eq!(warning(2.79, 3.4), Some(CellWarning::Low));
+        assert_eq!(warning(3.2, 3.66), Some(CellWarning::High));
+        assert_eq!(warning(2.7, 3.7), Some(CellWarning::Both));
+    }
+
+    #[test]
+    fn dc_balance_error_is_zero_when_balanced() {
+        assert_eq!(
+            dc_balance_error(Some(1500.), Some(1800.), Some(300.)),
+            Some(0.)
+        );
+    }
+
+    #[test]
+    fn dc_balance_error_shows_unmetered_power() {
+        assert_eq!(
+            dc_balance_error(Some(1000.), Some(1800.), Some(300.)),
+            Some(-500.)
+        );
+        assert_eq!(dc_balance_error(Some(1000.), None, Some(300.)), None);
+    }
+
+    #[test]
+    fn export_starts_past_the_deadband() {
+        assert!(!is_exporting(-50., 50.));
+        assert!(is_exporting(-50.1, 50.));
+        assert!(!is_exporting(20., 50.));
+        assert!(is_exporting(-50.1, -50.));
+    }
+
+    #[test]
+    fn frequency_shift_starts_past_the_threshold() {
+        assert!(!is_frequency_shifting(50.5, 50., 0.5));
+        assert!(is_frequency_shifting(50.51, 50., 0.5));
+        assert!(!is_frequency_shifting(49.5, 50., 0.5));
+    }
+}
@@ -0,0 +1,43 @@
+//! Whole-system power flow.
+
+use crate::GxDevice;
+
+/// Power flowing between the parts of the system, in watts. A field is `None`
+/// when no source for it could be read.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PowerFlow {
+    /// Positive when importing from the grid, negative when exporting.
+    pub grid: Option<f32>,
+    /// PV production, DC- and AC-coupled combined.
+    pub pv: Option<f32>,
+    /// Positive when the battery is charging, negative when discharging.
+    pub battery: Option<f32>,
+    /// AC consumption.
+    pub load: Option<f32>,
+}
+
+impl GxDevice {
+    /// Reads the power flow, preferring the system service's totals and
+    /// falling back to the VE.Bus and battery readings when they're missing.
+    pub async fn power_flow(&self) -> PowerFlow {
+        let grid = match self.get_grid_power().await {
+            Ok(grid) => Some(grid),
+            Err(_) => self.get_input_power().await.ok(),
+        };
+        let battery = match self.get_system_battery_power().await {
+            Ok(battery) => Some(battery),
+            Err(_) => self.get_battery_power().await.ok(),
+        };
+        let load = match self.get_consumption_power().await {
+            Ok(load) => Some(load),
+            Err(_) => self.get_output_power().await.ok(),
+        };
+
+        PowerFlow {
+            grid,
+            pv: self.get_total_pv_power().await.ok(),
+            battery,
+            load,
+        }
+    }
+}
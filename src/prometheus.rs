@@ -0,0 +1,105 @@
+//! Prometheus text exposition of the core readings.
+
+use crate::{format_value, GxDevice, GX_CAN_BUS_BMS, GX_VE_DEVICE_0};
+
+/// Name, help text, Modbus unit id and reading of one gauge.
+type Gauge = (&'static str, &'static str, u8, Result<f32, anyhow::Error>);
+
+/// Renders gauges in the Prometheus text format, leaving out failed readings.
+fn render_gauges(gauges: Vec<Gauge>, precision: Option<usize>) -> String {
+    let mut out = String::new();
+    for (name, help, unit_id, value) in gauges {
+        if let Ok(value) = value {
+            out.push_str(&format!("# HELP {} {}\n", name, help));
+            out.push_str(&format!("# TYPE {} gauge\n", name));
+            out.push_str(&format!(
+                "{}{{unit_id=\"{}\"}} {}\n",
+                name,
+                unit_id,
+                format_value(value, precision)
+            ));
+        }
+    }
+    out
+}
+
+impl GxDevice {
+    /// Renders the core readings as Prometheus gauges, labelled with the
+    /// Modbus unit id they were read from.
+    ///
+    /// Readings that fail are left out instead of being reported as zero.
+    pub async fn render_prometheus(&self) -> String {
+        let gauges = vec![
+            (
+                "victron_ac_input_power",
+                "AC input power in watts.",
+                self.vebus_device,
+                self.get_input_power().await,
+            ),
+            (
+                "victron_ac_output_power",
+                "AC output power in watts.",
+                self.vebus_device,
+                self.get_output_power().await,
+            ),
+            (
+                "victron_battery_power",
+                "Battery power in watts, positive when charging.",
+                GX_CAN_BUS_BMS,
+                self.get_battery_power().await,
+            ),
+            (
+                "victron_battery_soc",
+                "Battery state of charge in percent.",
+                GX_CAN_BUS_BMS,
+                self.get_battery_soc().await,
+            ),
+            (
+                "victron_pv_power_total",
+                "DC- and AC-coupled PV power in watts.",
+                GX_VE_DEVICE_0,
+                self.get_total_pv_power().await,
+            ),
+            (
+                "victron_ess_setpoint",
+                "ESS grid setpoint in watts.",
+                GX_VE_DEVICE_0,
+                self.get_setpoint().await,
+            ),
+        ];
+
+        render_gauges(gauges, self.export_precision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_gauges_and_skips_failed_readings() {
+        let gauges = vec![
+            ("victron_battery_soc", "Battery SOC.", 225, Ok(87.5)),
+            (
+                "victron_ess_setpoint",
+                "Setpoint.",
+                100,
+                Err(anyhow::Error::msg("timeout")),
+            ),
+        ];
+
+        assert_eq!(
+            render_gauges(gauges, None),
+            "# HELP victron_battery_soc Battery SOC.\n\
+             # TYPE victron_battery_soc gauge\n\
+             victron_battery_soc{unit_id=\"225\"} 87.5\n"
+        );
+    }
+
+    #[test]
+    fn applies_precision() {
+        let gauges = vec![("victron_battery_power", "Power.", 225, Ok(1234.567))];
+
+        assert!(render_gauges(gauges, Some(1)).ends_with("} 1234.6\n"));
+    }
+}
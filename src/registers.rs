@@ -0,0 +1,114 @@
+//! Descriptors of the registers the getters and setters map to.
+
+/// A register read (and possibly written) by this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterInfo {
+    /// Name of the value, matching the getter without its `get_` prefix.
+    pub name: &'static str,
+    /// Venus OS service the register belongs to (`com.victronenergy.<service>`).
+    pub service: &'static str,
+    /// Register address. For values summed over phases, the L1 register; L2
+    /// and L3 follow it.
+    pub address: u16,
+    /// Whether the crate has a setter for it.
+    pub writable: bool,
+}
+
+const fn reg(
+    name: &'static str,
+    service: &'static str,
+    address: u16,
+    writable: bool,
+) -> RegisterInfo {
+    RegisterInfo {
+        name,
+        service,
+        address,
+        writable,
+    }
+}
+
+const REGISTERS: &[RegisterInfo] = &[
+    reg("input_voltage", "vebus", 3, false),
+    reg("input_current", "vebus", 6, false),
+    reg("input_frequency", "vebus", 9, false),
+    reg("input_power", "vebus", 12, false),
+    reg("output_voltage", "vebus", 15, false),
+    reg("output_current", "vebus", 18, false),
+    reg("output_frequency", "vebus", 21, false),
+    reg("input_current_limit", "vebus", 22, true),
+    reg("output_power", "vebus", 23, false),
+    reg("phase_count", "vebus", 28, false),
+    reg("active_input", "vebus", 29, false),
+    reg("vebus_error", "vebus", 32, false),
+    reg("switch_position", "vebus", 33, true),
+    reg("temperature_alarm", "vebus", 34, false),
+    reg("low_battery_alarm", "vebus", 35, false),
+    reg("overload_alarm", "vebus", 36, false),
+    reg("ripple_alarm", "vebus", 47, false),
+    reg("grid_lost_alarm", "vebus", 64, false),
+    reg("battery_power", "battery", 258, false),
+    reg("battery_voltage", "battery", 259, false),
+    reg("starter_battery_voltage", "battery", 260, false),
+    reg("battery_current", "battery", 261, false),
+    reg("battery_temperature", "battery", 262, false),
+    reg("battery_soc", "battery", 266, false),
+    reg("battery_soh", "battery", 304, false),
+    reg("battery_max_charge_voltage", "battery", 305, false),
+    reg("battery_max_charge_current", "battery", 307, false),
+    reg("battery_max_discharge_current", "battery", 308, false),
+    reg("solar_charger_yield_today", "solarcharger", 784, false),
+    reg("solar_charger_yield_yesterday", "solarcharger", 786, false),
+    reg("serial", "system", 800, false),
+    reg("ac_pv_on_output_power", "system", 808, false),
+    reg("ac_pv_on_grid_power", "system", 811, false),
+    reg("ac_pv_on_genset_power", "system", 814, false),
+    reg("consumption_power", "system", 817, false),
+    reg("grid_power", "system", 820, false),
+    reg("active_input_source", "system", 826, false),
+    reg("system_time", "system", 830, false),
+    reg("system_battery_voltage", "system", 840, false),
+    reg("system_battery_current", "system", 841, false),
+    reg("system_battery_power", "system", 842, false),
+    reg("system_battery_soc", "system", 843, false),
+    reg("dc_pv_power", "system", 850, false),
+    reg("dc_system_power", "system", 860, false),
+    reg("solar_power", "system", 866, false),
+    reg("pv_inverter_voltage", "pvinverter", 1026, false),
+    reg("pv_inverter_power", "pvinverter", 1028, false),
+    reg("battery_min_cell_voltage", "battery", 1290, false),
+    reg("battery_max_cell_voltage", "battery", 1291, false),
+    reg("setpoint", "settings", 2700, true),
+    reg("max_discharge_power", "settings", 2704, true),
+    reg("max_charge_current", "settings", 2705, true),
+    reg("feed_in_excess_dc_pv", "settings", 2707, true),
+    reg("feed_in_excess_ac_pv", "settings", 2708, true),
+    reg("keep_batteries_charged", "settings", 2900, true),
+    reg("min_soc_limit", "settings", 2901, true),
+    reg("battery_life_soc_limit", "settings", 2903, false),
+];
+
+/// Every register the crate reads or writes, sorted by address. Kept in sync
+/// with the getters by hand.
+pub fn handled_registers() -> &'static [RegisterInfo] {
+    REGISTERS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_known_registers() {
+        let registers = handled_registers();
+        assert!(!registers.is_empty());
+        assert!(registers.contains(&reg("battery_soc", "battery", 266, false)));
+        assert!(registers.contains(&reg("setpoint", "settings", 2700, true)));
+    }
+
+    #[test]
+    fn registers_are_sorted_by_address() {
+        let registers = handled_registers();
+        assert!(registers.windows(2).all(|w| w[0].address <= w[1].address));
+    }
+}
@@ -0,0 +1,156 @@
+//! Thin newtypes around raw readings so units can't be mixed up, enabled by
+//! the `units` feature.
+//!
+//! The typed getters below are named after the `get_*` getters without
+//! their prefix (`battery_voltage` for `get_battery_voltage`) and return
+//! these newtypes instead of a bare `f32`.
+//!
+//! Each type derefs to the inner `f32` and prints its unit symbol, e.g.
+//! `Volts(230.0)` renders as `230.0 V`. A precision given in the format
+//! string is honoured (`{:.2}` renders `230.00 V`).
+
+use std::{fmt, ops::Deref};
+
+use crate::GxDevice;
+
+macro_rules! unit {
+    ($(#[$doc:meta])* $name:ident, $symbol:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+        pub struct $name(pub f32);
+
+        impl Deref for $name {
+            type Target = f32;
+
+            fn deref(&self) -> &f32 {
+                &self.0
+            }
+        }
+
+        impl From<f32> for $name {
+            fn from(value: f32) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for f32 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let precision = f.precision().unwrap_or(1);
+                write!(f, "{:.*} {}", precision, self.0, $symbol)
+            }
+        }
+    };
+}
+
+unit!(
+    /// Power in watts.
+    Watts,
+    "W"
+);
+unit!(
+    /// Electric potential in volts.
+    Volts,
+    "V"
+);
+unit!(
+    /// Current in amperes.
+    Amps,
+    "A"
+);
+unit!(
+    /// Temperature in degrees Celsius.
+    Celsius,
+    "°C"
+);
+
+impl GxDevice {
+    /// VE.Bus AC input power.
+    pub async fn input_power(&self) -> Result<Watts, anyhow::Error> {
+        self.get_input_power().await.map(Watts)
+    }
+
+    /// VE.Bus AC output power.
+    pub async fn output_power(&self) -> Result<Watts, anyhow::Error> {
+        self.get_output_power().await.map(Watts)
+    }
+
+    /// Battery power, positive when charging.
+    pub async fn battery_power(&self) -> Result<Watts, anyhow::Error> {
+        self.get_battery_power().await.map(Watts)
+    }
+
+    /// Grid power summed over all phases, positive when importing.
+    pub async fn grid_power(&self) -> Result<Watts, anyhow::Error> {
+        self.get_grid_power().await.map(Watts)
+    }
+
+    /// AC consumption summed over all phases.
+    pub async fn consumption_power(&self) -> Result<Watts, anyhow::Error> {
+        self.get_consumption_power().await.map(Watts)
+    }
+
+    /// DC- and AC-coupled PV power combined.
+    pub async fn total_pv_power(&self) -> Result<Watts, anyhow::Error> {
+        self.get_total_pv_power().await.map(Watts)
+    }
+
+    /// VE.Bus AC input voltage (L1).
+    pub async fn input_voltage(&self) -> Result<Volts, anyhow::Error> {
+        self.get_input_voltage().await.map(Volts)
+    }
+
+    /// VE.Bus AC output voltage (L1).
+    pub async fn output_voltage(&self) -> Result<Volts, anyhow::Error> {
+        self.get_output_voltage().await.map(Volts)
+    }
+
+    /// Voltage of the main battery.
+    pub async fn battery_voltage(&self) -> Result<Volts, anyhow::Error> {
+        self.get_battery_voltage().await.map(Volts)
+    }
+
+    /// VE.Bus AC input current (L1).
+    pub async fn input_current(&self) -> Result<Amps, anyhow::Error> {
+        self.get_input_current().await.map(Amps)
+    }
+
+    /// VE.Bus AC output current (L1).
+    pub async fn output_current(&self) -> Result<Amps, anyhow::Error> {
+        self.get_output_current().await.map(Amps)
+    }
+
+    /// Current of the main battery, positive when charging.
+    pub async fn battery_current(&self) -> Result<Amps, anyhow::Error> {
+        self.get_battery_current().await.map(Amps)
+    }
+
+    /// Battery temperature reported by the BMS.
+    pub async fn battery_temperature(&self) -> Result<Celsius, anyhow::Error> {
+        self.get_battery_temperature().await.map(Celsius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_prints_the_unit() {
+        assert_eq!(Volts(230.).to_string(), "230.0 V");
+        assert_eq!(Watts(-1500.).to_string(), "-1500.0 W");
+        assert_eq!(Amps(12.34).to_string(), "12.3 A");
+        assert_eq!(Celsius(25.).to_string(), "25.0 °C");
+    }
+
+    #[test]
+    fn display_honours_precision() {
+        assert_eq!(format!("{:.2}", Volts(230.)), "230.00 V");
+        assert_eq!(format!("{:.0}", Watts(1234.5)), "1234 W");
+    }
+}
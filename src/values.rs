@@ -0,0 +1,95 @@
+//! Generic listing of every modelled reading.
+
+use crate::GxDevice;
+
+impl GxDevice {
+    /// Reads every numeric value the crate models that doesn't need a unit id
+    /// argument, as `(name, value)` pairs. Names are stable and match the
+    /// getter names without their `get_` prefix; failed readings are skipped.
+    ///
+    /// This performs one Modbus request per value. Reading the SOH updates
+    /// [`GxDevice::soh_delta`].
+    pub async fn values(&self) -> Vec<(&'static str, f32)> {
+        let values = [
+            ("input_power", self.get_input_power().await),
+            ("input_voltage", self.get_input_voltage().await),
+            ("input_current", self.get_input_current().await),
+            ("input_frequency", self.get_input_frequency().await),
+            ("input_current_limit", self.get_input_current_limit().await),
+            ("output_power", self.get_output_power().await),
+            ("output_voltage", self.get_output_voltage().await),
+            ("output_current", self.get_output_current().await),
+            ("output_frequency", self.get_output_frequency().await),
+            ("battery_power", self.get_battery_power().await),
+            ("battery_voltage", self.get_battery_voltage().await),
+            ("battery_current", self.get_battery_current().await),
+            ("battery_temperature", self.get_battery_temperature().await),
+            ("battery_soc", self.get_battery_soc().await),
+            ("battery_soh", self.get_battery_soh().await),
+            (
+                "battery_max_charge_voltage",
+                self.get_battery_max_charge_voltage().await,
+            ),
+            (
+                "battery_max_charge_current",
+                self.get_battery_max_charge_current().await,
+            ),
+            (
+                "battery_max_discharge_current",
+                self.get_battery_max_discharge_current().await,
+            ),
+            (
+                "battery_min_cell_voltage",
+                self.get_battery_min_cell_voltage().await,
+            ),
+            (
+                "battery_max_cell_voltage",
+                self.get_battery_max_cell_voltage().await,
+            ),
+            (
+                "starter_battery_voltage",
+                self.get_starter_battery_voltage().await,
+            ),
+            (
+                "system_battery_voltage",
+                self.get_system_battery_voltage().await,
+            ),
+            (
+                "system_battery_current",
+                self.get_system_battery_current().await,
+            ),
+            (
+                "system_battery_power",
+                self.get_system_battery_power().await,
+            ),
+            ("system_battery_soc", self.get_system_battery_soc().await),
+            ("solar_power", self.get_solar_power().await),
+            ("dc_pv_power", self.get_dc_pv_power().await),
+            ("dc_system_power", self.get_dc_system_power().await),
+            (
+                "ac_pv_on_output_power",
+                self.get_ac_pv_on_output_power().await,
+            ),
+            ("ac_pv_on_grid_power", self.get_ac_pv_on_grid_power().await),
+            (
+                "ac_pv_on_genset_power",
+                self.get_ac_pv_on_genset_power().await,
+            ),
+            ("grid_power", self.get_grid_power().await),
+            ("consumption_power", self.get_consumption_power().await),
+            ("setpoint", self.get_setpoint().await),
+            ("min_soc_limit", self.get_min_soc_limit().await),
+            (
+                "battery_life_soc_limit",
+                self.get_battery_life_soc_limit().await,
+            ),
+            ("max_discharge_power", self.get_max_discharge_power().await),
+            ("max_charge_current", self.get_max_charge_current().await),
+        ];
+
+        values
+            .into_iter()
+            .filter_map(|(name, value)| value.ok().map(|value| (name, value)))
+            .collect()
+    }
+}